@@ -43,6 +43,11 @@ pub mod __context {
         ctx.insert(key.into(), value);
     }
 
+    #[inline(always)]
+    pub fn add_dynamic(ctx: &MutableMap, key: Value, value: Value) {
+        ctx.insert(key.into(), value);
+    }
+
     #[inline(always)]
     pub fn build(ctx: MutableMap) -> Value {
         Value::from_object(ctx)
@@ -54,6 +59,47 @@ pub mod __context {
         }
         ENV.with(|x| x.clone())
     }
+
+    /// Renders `source` against `ctx` reusing a thread-local compiled template.
+    ///
+    /// The template is compiled once per distinct source string and kept in a
+    /// thread-local [`Environment`] under a name derived from a hash of the
+    /// source bytes, so repeated calls with the same source skip re-parsing.
+    /// The template name is a pure function of the hash, so the presence of a
+    /// cached entry is detected with a single `get_template` probe and no side
+    /// table is needed.
+    ///
+    /// Note that the cache is keyed on the 64-bit hash digest of the source:
+    /// two distinct sources that collide under that hash would map to the same
+    /// compiled template.  A collision is astronomically unlikely in practice
+    /// but is not impossible.
+    pub fn render_cached(source: &str, ctx: Value) -> String {
+        use std::cell::RefCell;
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::Hasher;
+
+        thread_local! {
+            static CACHED_ENV: RefCell<Environment<'static>> = RefCell::new(Environment::new());
+        }
+
+        let mut hasher = DefaultHasher::new();
+        hasher.write(source.as_bytes());
+        let hash = hasher.finish();
+        let name = format!("cached_template_{hash:016x}");
+
+        CACHED_ENV.with(|env| {
+            if env.borrow().get_template(&name).is_err() {
+                env.borrow_mut()
+                    .add_template_owned(name.clone(), source.to_string())
+                    .expect("failed to compile template");
+            }
+            let env = env.borrow();
+            env.get_template(&name)
+                .expect("cached template missing")
+                .render(ctx, &[])
+                .expect("failed to render expression")
+        })
+    }
 }
 
 /// Creates a template context from keys and values or merging in another value.
@@ -181,6 +227,89 @@ macro_rules! __context_pair {
     };
 }
 
+/// Creates a [`Value`](crate::value::Value) map from dynamic keys and values.
+///
+/// Unlike [`context!`](crate::context!), which only accepts identifier keys,
+/// `dict!` takes arbitrary key and value expressions.  This makes it possible
+/// to build maps whose keys are computed at runtime:
+///
+/// ```rust
+/// # use minijinja::dict;
+/// let key = "name".to_string();
+/// let ctx = dict! {
+///     key => "Peter",
+///     format!("greeting") => "Hello",
+/// };
+/// ```
+///
+/// An empty map can be created with `dict!{}` and, like
+/// [`context!`](crate::context!), other values can be merged in with a leading
+/// `..` operator (order of precedence is left to right):
+///
+/// ```rust
+/// # use minijinja::dict;
+/// let base = dict! { "a".to_string() => "A" };
+/// let ctx = dict! {
+///     "b".to_string() => "B",
+///     ..base,
+/// };
+/// ```
+///
+/// Keys are converted with [`Value::from`](crate::value::Value::from) and
+/// values go through [`Value::from_serialize`](crate::value::Value::from_serialize),
+/// matching the behavior of [`context!`](crate::context!).  The resulting
+/// object is backed by a [`MutableMap`](crate::value::mutable_map::MutableMap)
+/// and behaves identically to a `context!` map in templates.
+#[macro_export]
+macro_rules! dict {
+    () => {
+        $crate::__context::build($crate::__context::make())
+    };
+    (
+        @acc [$(($key:expr, $value:expr))*]
+        $(.. $ctx:expr),* $(,)?
+    ) => {{
+        let _guard = $crate::__context::value_optimization();
+        let mut ctx = $crate::__context::make();
+        $(
+            $crate::__context::add_dynamic(
+                &mut ctx,
+                $crate::value::Value::from($key),
+                $crate::value::Value::from_serialize(&$value),
+            );
+        )*
+        let ctx = $crate::__context::build(ctx);
+        let mut merged_ctx = ::std::vec::Vec::new();
+        $(
+            merged_ctx.push($crate::value::Value::from($ctx));
+        )*
+        if merged_ctx.is_empty() {
+            ctx
+        } else {
+            merged_ctx.insert(0, ctx);
+            $crate::value::Value::from_object($crate::__context::MergeObject(merged_ctx))
+        }
+    }};
+    (
+        $(.. $ctx:expr),* $(,)?
+    ) => {{
+        let _guard = $crate::__context::value_optimization();
+        let mut ctx = ::std::vec::Vec::new();
+        $(
+            ctx.push($crate::value::Value::from($ctx));
+        )*;
+        $crate::value::Value::from_object($crate::__context::MergeObject(ctx))
+    }};
+    // Expression keys can start with `..`, so pairs are peeled one at a time
+    // into the `@acc` list before any trailing `..` merges are matched.
+    ($key:expr => $($rest:tt)*) => {
+        $crate::dict!(@acc [] $key => $($rest)*)
+    };
+    (@acc [$($done:tt)*] $key:expr => $value:expr $(, $($rest:tt)*)?) => {
+        $crate::dict!(@acc [$($done)* ($key, $value)] $($($rest)*)?)
+    };
+}
+
 /// An utility macro to create arguments for function calls.
 ///
 /// This creates a slice of values on the stack which can be
@@ -203,6 +332,28 @@ macro_rules! __context_pair {
 /// value.call(state, args!(1, 2, foo => "bar"), &[Rc::new(DefaultRenderingEventListener::default())]);
 /// ```
 ///
+/// In addition to statically written arguments the macro supports spreading
+/// dynamic values into the call.  A `..expr` splat expands an
+/// `IntoIterator<Item = Value>` into positional slots, and a `**expr` splat
+/// merges an `IntoIterator<Item = (&str, Value)>` into the keyword group:
+///
+/// ```
+/// # use minijinja::{value::Value, args, Environment, listener::DefaultRenderingEventListener};
+/// # use std::rc::Rc;
+/// # let env = Environment::default();
+/// # let state = &env.empty_state();
+/// # let value = Value::from(());
+/// let extra = vec![Value::from(2), Value::from(3)];
+/// let dynamic_kwargs = vec![("foo", Value::from("bar"))];
+/// value.call(state, args!(1, ..extra, baz => 4, **dynamic_kwargs), &[Rc::new(DefaultRenderingEventListener::default())]);
+/// ```
+///
+/// Positional splats must precede any keyword arguments or keyword splats.
+/// Multiple `**` groups merge left to right with later keys overriding
+/// earlier ones.  Note that a `**` splat expects an iterator of
+/// `(&str, Value)` pairs; to spread an existing [`Kwargs`](crate::value::Kwargs)
+/// convert it into such an iterator first.
+///
 /// Note that this like [`context!`](crate::context) goes through
 /// [`Value::from_serialize`](crate::value::Value::from_serialize).
 #[macro_export]
@@ -223,6 +374,10 @@ macro_rules! __args_helper {
     (branch [[]], $args:tt) => { $crate::__args_helper!(args $args) };
     (branch [[$n:ident => $e:expr]], $args:tt) => { $crate::__args_helper!(kwargs $args) };
     (branch [[$n:ident => $e:expr, $($r:tt)*]], $args:tt) => { $crate::__args_helper!(kwargs $args) };
+    (branch [[** $e:expr]], $args:tt) => { $crate::__args_helper!(kwargs $args) };
+    (branch [[** $e:expr, $($r:tt)*]], $args:tt) => { $crate::__args_helper!(kwargs $args) };
+    (branch [[.. $e:expr]], $args:tt) => { $crate::__args_helper!(args $args) };
+    (branch [[.. $e:expr, $($rest:tt)*]], $args:tt) => { $crate::__args_helper!(branch [[$($rest)*]], $args) };
     (branch [[$e:expr]], $args:tt) => { $crate::__args_helper!(args $args) };
     (branch [[$e:expr, $($rest:tt)*]], $args:tt) => { $crate::__args_helper!(branch [[$($rest)*]], $args) };
 
@@ -252,6 +407,20 @@ macro_rules! __args_helper {
         $kwargs.push((stringify!($name), $crate::value::Value::from_serialize(&$expr)));
         $crate::__args_helper!(peel $args, $kwargs, true, [$($rest)*]);
     };
+    (peel $args:ident, $kwargs:ident, $has_kwargs:ident, [** $expr:expr]) => {
+        $kwargs.extend(::std::iter::IntoIterator::into_iter($expr));
+    };
+    (peel $args:ident, $kwargs:ident, $has_kwargs:ident, [** $expr:expr, $($rest:tt)*]) => {
+        $kwargs.extend(::std::iter::IntoIterator::into_iter($expr));
+        $crate::__args_helper!(peel $args, $kwargs, true, [$($rest)*]);
+    };
+    (peel $args:ident, $kwargs:ident, false, [.. $expr:expr]) => {
+        $args.extend(::std::iter::IntoIterator::into_iter($expr));
+    };
+    (peel $args:ident, $kwargs:ident, false, [.. $expr:expr, $($rest:tt)*]) => {
+        $args.extend(::std::iter::IntoIterator::into_iter($expr));
+        $crate::__args_helper!(peel $args, $kwargs, false, [$($rest)*]);
+    };
     (peel $args:ident, $kwargs:ident, false, [$expr:expr]) => {
         $args.push($crate::value::Value::from_serialize(&$expr));
     };
@@ -322,6 +491,56 @@ macro_rules! render {
     }
 }
 
+/// A caching variant of the [`render!`](crate::render) macro.
+///
+/// This behaves exactly like [`render!`](crate::render) but avoids re-parsing
+/// the template on every invocation.  The default form compiles the template
+/// once per distinct source string into a thread-local
+/// [`Environment`](crate::Environment) keyed by a hash of the source and reuses
+/// it on subsequent calls, which is useful in hot paths such as logging or
+/// row-by-row rendering.
+///
+/// # Example
+///
+/// ```
+/// # use minijinja::render_cached;
+/// for name in ["World", "Peter"] {
+///     println!("{}", render_cached!("Hello {{ name }}!", name));
+/// }
+/// ```
+///
+/// As with [`render!`](crate::render) a custom environment can be supplied with
+/// the `in env` form.  In that case the template is parsed once per call
+/// against the provided environment (no caching is performed):
+///
+/// ```
+/// # use minijinja::{Environment, render_cached};
+/// let env = Environment::new();
+/// println!("{}", render_cached!(in env, "Hello {{ name }}!", name => "World"));
+/// ```
+///
+/// # Panics
+///
+/// This macro panics if the format string is an invalid template or the
+/// template evaluation failed.
+#[macro_export]
+macro_rules! render_cached {
+    (
+        in $env:expr,
+        $tmpl:expr
+        $(, $key:ident $(=> $value:expr)?)* $(,)?
+    ) => {
+        ($env).render_str($tmpl, $crate::context! { $($key $(=> $value)? ,)* }, &[])
+            .expect("failed to render expression")
+    };
+    (
+        $tmpl:expr
+        $(, $key:ident $(=> $value:expr)?)* $(,)?
+    ) => {
+        $crate::__context::render_cached($tmpl, $crate::context! { $($key $(=> $value)? ,)* })
+    };
+}
+
 /// Report MinijinjaError
 #[macro_export]
 macro_rules! jinja_err {
@@ -334,6 +553,75 @@ macro_rules! jinja_err {
     };
 }
 
+/// Returns early with a [`MinijinjaError`] built from a kind and message.
+///
+/// This is the error equivalent of a guard-style early return and mirrors
+/// anyhow's `bail!`.  It builds on [`jinja_err!`](crate::jinja_err) and comes
+/// in two forms.
+///
+/// The context-free form is meant for pure-Rust code and expands to
+/// `return jinja_err!(kind, ..)`:
+///
+/// ```ignore
+/// bail!(ErrorKind::InvalidOperation, "cannot {} here", what);
+/// ```
+///
+/// The state-aware form takes a leading [`State`](crate::State) (or listener
+/// slice) and routes the error through the engine's `attach_basic_debug_info`
+/// machinery so it records the originating template name and line:
+///
+/// ```ignore
+/// bail!(state, ErrorKind::InvalidOperation, "cannot {} here", what);
+/// ```
+///
+/// Like [`jinja_err!`](crate::jinja_err) this macro is unhygienic: it expects
+/// `MinijinjaError` to be in scope at the call site, and the state-aware form
+/// additionally expects `attach_basic_debug_info` to be in scope.
+#[macro_export]
+macro_rules! bail {
+    ($state:expr, $kind:path, $msg:expr $(,)?) => {
+        return attach_basic_debug_info(Err(MinijinjaError::new($kind, $msg)), $state)
+    };
+    ($state:expr, $kind:path, $fmt:expr, $($arg:tt)*) => {
+        return attach_basic_debug_info(
+            Err(MinijinjaError::new($kind, format!($fmt, $($arg)*))),
+            $state,
+        )
+    };
+    ($kind:expr, $($arg:tt)*) => {
+        return $crate::jinja_err!($kind, $($arg)*)
+    };
+}
+
+/// Returns early with a [`MinijinjaError`] unless a condition holds.
+///
+/// This is the error equivalent of an assertion guard and mirrors anyhow's
+/// `ensure!`.  When the condition is `false` it delegates to
+/// [`bail!`](crate::bail), so both the context-free and state-aware forms are
+/// available:
+///
+/// ```ignore
+/// ensure!(!args.is_empty(), ErrorKind::InvalidOperation, "expected at least one argument");
+/// ensure!(n > 0, state, ErrorKind::InvalidOperation, "n must be positive, got {}", n);
+/// ```
+///
+/// Like [`bail!`](crate::bail) this macro is unhygienic: it expects
+/// `MinijinjaError` to be in scope at the call site, and the state-aware form
+/// additionally expects `attach_basic_debug_info` to be in scope.
+#[macro_export]
+macro_rules! ensure {
+    ($cond:expr, $state:expr, $kind:path, $($arg:tt)*) => {
+        if !($cond) {
+            $crate::bail!($state, $kind, $($arg)*);
+        }
+    };
+    ($cond:expr, $kind:expr, $($arg:tt)*) => {
+        if !($cond) {
+            $crate::bail!($kind, $($arg)*);
+        }
+    };
+}
+
 /// Creates a [`Vec`] containing the arguments (alias for the standard vec! macro).
 ///
 /// `tuple!` is an alias for the standard `vec!` macro, allowing `Vec`s to be defined
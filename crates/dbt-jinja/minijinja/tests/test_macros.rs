@@ -0,0 +1,108 @@
+use minijinja::value::{Kwargs, Value};
+use minijinja::{args, dict, ensure, render_cached, Environment, Error as MinijinjaError, ErrorKind};
+
+#[test]
+fn test_args_positional_splat_precedes_kwargs() {
+    let extra = vec![Value::from(2), Value::from(3)];
+    let a = args!(1, ..extra, foo => 4);
+    // three positional slots (1, 2, 3) followed by the trailing kwargs group
+    assert_eq!(a.len(), 4);
+    assert_eq!(a[0], Value::from(1));
+    assert_eq!(a[1], Value::from(2));
+    assert_eq!(a[2], Value::from(3));
+    let kwargs = Kwargs::try_from(a[3].clone()).unwrap();
+    assert_eq!(kwargs.get::<i64>("foo").unwrap(), 4);
+}
+
+#[test]
+fn test_args_kwargs_splat_later_overrides() {
+    let dynamic = vec![("foo", Value::from(2))];
+    let a = args!(foo => 1, **dynamic);
+    let kwargs = Kwargs::try_from(a.last().unwrap().clone()).unwrap();
+    assert_eq!(kwargs.get::<i64>("foo").unwrap(), 2);
+}
+
+#[test]
+fn test_args_positional_only_has_no_kwargs() {
+    let extra = vec![Value::from(1), Value::from(2)];
+    let a = args!(..extra);
+    assert_eq!(a.len(), 2);
+    // no trailing Kwargs was pushed
+    assert!(Kwargs::try_from(a[1].clone()).is_err());
+}
+
+fn render_with(tmpl: &str, ctx: Value) -> String {
+    Environment::new().render_str(tmpl, ctx, &[]).unwrap()
+}
+
+#[test]
+fn test_dict_dynamic_keys() {
+    let key = "name".to_string();
+    let ctx = dict! {
+        key => "Peter",
+        format!("greeting_{}", 1) => "Hello",
+    };
+    assert_eq!(
+        render_with("{{ name }}/{{ greeting_1 }}", ctx),
+        "Peter/Hello"
+    );
+}
+
+#[test]
+fn test_dict_merge_precedence() {
+    let base = dict! { "a".to_string() => "base", "b".to_string() => "base" };
+    let ctx = dict! {
+        "b".to_string() => "override",
+        ..base,
+    };
+    // left to right precedence: the leading pairs win over the merged map
+    assert_eq!(render_with("{{ a }}/{{ b }}", ctx), "base/override");
+}
+
+#[test]
+fn test_dict_empty() {
+    let ctx = dict! {};
+    assert_eq!(render_with("{{ x is defined }}", ctx), "false");
+}
+
+#[test]
+fn test_render_cached_reuses_template() {
+    // the same source rendered repeatedly (cache hit) keeps producing the
+    // right output
+    for name in ["World", "Peter"] {
+        let rendered = render_cached!("Hello {{ name }}!", name);
+        assert_eq!(rendered, format!("Hello {name}!"));
+    }
+    // two distinct literals must not collide
+    assert_eq!(render_cached!("a={{ v }}", v => 1), "a=1");
+    assert_eq!(render_cached!("b={{ v }}", v => 1), "b=1");
+}
+
+#[test]
+fn test_render_cached_in_env_fallthrough() {
+    let env = Environment::new();
+    assert_eq!(
+        render_cached!(in env, "Hello {{ name }}!", name => "World"),
+        "Hello World!"
+    );
+}
+
+fn guard(n: i64) -> Result<i64, MinijinjaError> {
+    ensure!(
+        n > 0,
+        ErrorKind::InvalidOperation,
+        "n must be positive, got {}",
+        n
+    );
+    Ok(n)
+}
+
+#[test]
+fn test_ensure_passes_and_bails() {
+    // true condition falls through
+    assert_eq!(guard(1).unwrap(), 1);
+    // false condition returns the error early
+    let err = guard(-1).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidOperation);
+    assert!(err.to_string().contains("n must be positive, got -1"));
+}